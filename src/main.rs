@@ -1,26 +1,59 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use dotenv::dotenv;
-use lettre::smtp::authentication::IntoCredentials;
-use lettre::{SmtpClient, SmtpTransport, Transport};
-use lettre_email::EmailBuilder;
 use log::{info, trace};
 use serde::Deserialize;
 use std::env;
+use std::sync::Arc;
 use tokio::time::interval;
 
-#[derive(Debug, Deserialize)]
+mod notifier;
+mod retry;
+mod source;
+mod spool;
+mod subscriber;
+
+use notifier::{Email, Notifier, NotifierConfig};
+use source::Source;
+use spool::Spool;
+use subscriber::Subscriber;
+
+#[derive(Debug, PartialEq, Eq, Hash)]
 enum Area {
     Bronx,
     Brooklyn,
     Manhattan,
     Queens,
-    #[serde(rename = "Staten Island")]
     StatenIsland,
-    #[serde(rename = "Long Island")]
     LongIsland,
-    #[serde(rename = "Mid-Hudson")]
     MidHudson,
+    Other(String),
+}
+
+impl From<String> for Area {
+    fn from(string: String) -> Self {
+        match string.as_str() {
+            "Bronx" => Area::Bronx,
+            "Brooklyn" => Area::Brooklyn,
+            "Manhattan" => Area::Manhattan,
+            "Queens" => Area::Queens,
+            "Staten Island" => Area::StatenIsland,
+            "Long Island" => Area::LongIsland,
+            "Mid-Hudson" => Area::MidHudson,
+            _ => Area::Other(string),
+        }
+    }
+}
+
+// Parse any label into a known borough or an `Other` catch-all so a new region
+// in the feed doesn't hard-fail deserialization and silently stop notifications.
+impl<'de> Deserialize<'de> for Area {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(Area::from(String::deserialize(deserializer)?))
+    }
 }
 
 impl std::fmt::Display for Area {
@@ -33,6 +66,7 @@ impl std::fmt::Display for Area {
             Area::StatenIsland => "Staten Island",
             Area::LongIsland => "Long Island",
             Area::MidHudson => "Mid-Hudson",
+            Area::Other(area) => area,
         };
         write!(f, "{}", string)
     }
@@ -57,7 +91,7 @@ struct Location {
     updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 enum PortalType {
     Clinic,
@@ -81,49 +115,41 @@ struct Response {
     portals: Vec<Portal>,
 }
 
-struct Email {
-    email: String,
-    mailer: SmtpTransport,
+/// Load the global notification backends that fire for every new slot.
+///
+/// A JSON list of [`NotifierConfig`] is read from `NOTIFIERS_PATH` when set,
+/// e.g. Slack/Discord webhooks. Per-subscriber email delivery is handled
+/// separately by [`load_subscribers`], so this defaults to an empty list.
+fn load_notifiers() -> Result<Vec<Arc<dyn Notifier>>> {
+    let configs: Vec<NotifierConfig> = match env::var("NOTIFIERS_PATH") {
+        Ok(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        Err(_) => Vec::new(),
+    };
+    configs
+        .into_iter()
+        .map(|config| Ok(Arc::from(config.build()?)))
+        .collect()
 }
 
-impl Email {
-    fn new(email: &str, password: &str) -> Result<Self> {
-        let credentials = (email, password).into_credentials();
-        let mailer: SmtpTransport = SmtpClient::new_simple("smtp.gmail.com")?
-            .credentials(credentials)
-            .transport();
-        Ok(Self {
-            email: email.to_string(),
-            mailer,
+/// Load the subscribers and pair each with an Email notifier addressed to them.
+///
+/// Subscribers are read from `SUBSCRIBERS_PATH` when set; otherwise we fall
+/// back to a single unfiltered subscriber at `LETTRE_EMAIL`. Every subscriber's
+/// mail is sent from the shared `LETTRE_EMAIL`/`LETTRE_PASSWORD` account.
+fn load_subscribers() -> Result<Vec<(Subscriber, Arc<Email>)>> {
+    let sender = env::var("LETTRE_EMAIL")?;
+    let password = env::var("LETTRE_PASSWORD")?;
+    let subscribers: Vec<Subscriber> = match env::var("SUBSCRIBERS_PATH") {
+        Ok(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        Err(_) => serde_json::from_value(serde_json::json!([{ "email": sender }]))?,
+    };
+    subscribers
+        .into_iter()
+        .map(|subscriber| {
+            let mailer = Arc::new(Email::to(&sender, &password, &subscriber.email)?);
+            Ok((subscriber, mailer))
         })
-    }
-
-    fn notify(&mut self, location: &Location, portal: Option<&Portal>) -> Result<()> {
-        let mut body = format!(
-            "Found {} vaccine appoitnemnt(s)! The location is {} in {}.\n",
-            location.appointments.count, location.name, location.area
-        );
-        if let Some(details) = &location.appointments.summary {
-            body.push_str(details);
-            body.push_str("\n");
-        }
-        if let Some(portal) = portal {
-            body.push_str(&format!(
-                "Appointments can be booked through the {} portal, at {}",
-                portal.name, portal.url
-            ));
-        } else {
-            body.push_str("Visit turbovax.info for more information");
-        }
-        let email = EmailBuilder::new()
-            .from(self.email.as_str())
-            .to(self.email.as_str())
-            .subject("Vaccine slot found!")
-            .body(body)
-            .build()?
-            .into();
-        self.mailer.send(email).map(|_| ()).map_err(From::from)
-    }
+        .collect()
 }
 
 #[tokio::main]
@@ -131,31 +157,60 @@ async fn main() -> Result<()> {
     dotenv()?;
     env_logger::init();
 
-    let mut client = Email::new(&env::var("LETTRE_EMAIL")?, &env::var("LETTRE_PASSWORD")?)?;
-    let mut interval = interval(std::time::Duration::from_secs(60));
+    let notifiers = load_notifiers()?;
+    let subscribers = load_subscribers()?;
+    let mut spool = Spool::load(env::var("SPOOL_PATH").unwrap_or_else(|_| "spool.json".to_string()))?;
+    let source = Source::from_env()?;
+    let mut interval = interval(source.interval);
 
     loop {
         interval.tick().await;
         trace!("Evaluating");
-        let res = reqwest::get("https://turbovax.global.ssl.fastly.net/dashboard").await?;
+        let res = reqwest::get(&source.url).await?;
         let res: Response = serde_json::from_str(&res.text().await?)?;
-        let locations = res.locations.iter().filter_map(|location| {
-            if location.available {
-                Some(location)
-            } else {
-                None
+        let res = Arc::new(res);
+        let available: Vec<usize> = res
+            .locations
+            .iter()
+            .enumerate()
+            .filter(|(_, location)| location.available)
+            .map(|(index, _)| index)
+            .collect();
+        for &index in &available {
+            let location = &res.locations[index];
+            if !spool.observe(location) {
+                continue;
             }
-        });
-        for location in locations {
-            let portal = res
-                .portals
-                .iter()
-                .find(|portal| portal.key == location.portal);
+            let portal_index = res.portals.iter().position(|p| p.key == location.portal);
+            let portal = portal_index.map(|p| &res.portals[p]);
             info!(
                 "Appointment found. Location: {:?}, portal: {:?}",
                 location, portal
             );
-            client.notify(location, portal)?;
+            // Fan each delivery out onto its own task so retries/backoff never
+            // block the polling loop, and index into the shared response so the
+            // task can borrow the location and portal without cloning them.
+            for notifier in &notifiers {
+                let (res, notifier) = (res.clone(), notifier.clone());
+                tokio::spawn(async move {
+                    let location = &res.locations[index];
+                    let portal = portal_index.map(|p| &res.portals[p]);
+                    retry::deliver(&*notifier, location, portal).await;
+                });
+            }
+            for (subscriber, mailer) in &subscribers {
+                if !subscriber.matches(location, portal) {
+                    continue;
+                }
+                let (res, mailer) = (res.clone(), mailer.clone());
+                tokio::spawn(async move {
+                    let location = &res.locations[index];
+                    let portal = portal_index.map(|p| &res.portals[p]);
+                    retry::deliver(&*mailer, location, portal).await;
+                });
+            }
         }
+        spool.prune(available.iter().map(|&index| res.locations[index].id.as_str()));
+        spool.save()?;
     }
 }
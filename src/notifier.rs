@@ -0,0 +1,148 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use lettre::smtp::authentication::IntoCredentials;
+use lettre::{SmtpClient, SmtpTransport, Transport};
+use lettre_email::EmailBuilder;
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+
+use crate::{Location, Portal};
+
+/// A destination that availability events are delivered to.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, location: &Location, portal: Option<&Portal>) -> Result<()>;
+}
+
+/// Serde-driven description of a single notification backend.
+///
+/// The enum is `untagged` so a config file lists backends by their fields
+/// alone, e.g. `{"email": "...", "password": "..."}` for Gmail SMTP or
+/// `{"url": "https://hooks.slack.com/..."}` for an incoming webhook.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum NotifierConfig {
+    Email { email: String, password: String },
+    Webhook { url: String },
+}
+
+impl NotifierConfig {
+    pub fn build(self) -> Result<Box<dyn Notifier>> {
+        Ok(match self {
+            NotifierConfig::Email { email, password } => Box::new(Email::new(&email, &password)?),
+            NotifierConfig::Webhook { url } => Box::new(Webhook::new(url)),
+        })
+    }
+}
+
+/// Render the human-readable message shared by every notifier.
+fn message(location: &Location, portal: Option<&Portal>) -> String {
+    let mut body = format!(
+        "Found {} vaccine appoitnemnt(s)! The location is {} in {}.\n",
+        location.appointments.count, location.name, location.area
+    );
+    if let Some(details) = &location.appointments.summary {
+        body.push_str(details);
+        body.push_str("\n");
+    }
+    if let Some(portal) = portal {
+        body.push_str(&format!(
+            "Appointments can be booked through the {} portal, at {}",
+            portal.name, portal.url
+        ));
+    } else {
+        body.push_str("Visit turbovax.info for more information");
+    }
+    body
+}
+
+pub struct Email {
+    email: String,
+    to: String,
+    mailer: Arc<Mutex<SmtpTransport>>,
+}
+
+impl Email {
+    /// Build a Gmail SMTP backend that sends from and to the same address.
+    pub fn new(email: &str, password: &str) -> Result<Self> {
+        Self::to(email, password, email)
+    }
+
+    /// Build a Gmail SMTP backend sending from `email` to an arbitrary `to`
+    /// recipient, used to deliver to individual subscribers.
+    pub fn to(email: &str, password: &str, to: &str) -> Result<Self> {
+        let credentials = (email, password).into_credentials();
+        let mailer = SmtpClient::new_simple("smtp.gmail.com")?
+            .credentials(credentials)
+            .transport();
+        Ok(Self {
+            email: email.to_string(),
+            to: to.to_string(),
+            mailer: Arc::new(Mutex::new(mailer)),
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for Email {
+    async fn notify(&self, location: &Location, portal: Option<&Portal>) -> Result<()> {
+        let email = EmailBuilder::new()
+            .from(self.email.as_str())
+            .to(self.to.as_str())
+            .subject("Vaccine slot found!")
+            .body(message(location, portal))
+            .build()?
+            .into();
+        // lettre's SMTP transport is synchronous; run the send on the blocking
+        // pool so a slow Gmail handshake never stalls the polling runtime. The
+        // transport built once in `to` is shared, but it keeps lettre's default
+        // connection settings, so each send still opens its own SMTP connection.
+        let mailer = self.mailer.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut mailer = mailer.lock().unwrap();
+            mailer.send(email).map(|_| ()).map_err(anyhow::Error::from)
+        })
+        .await??;
+        Ok(())
+    }
+}
+
+pub struct Webhook {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl Webhook {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for Webhook {
+    async fn notify(&self, location: &Location, portal: Option<&Portal>) -> Result<()> {
+        let payload = serde_json::json!({
+            "text": message(location, portal),
+            "location": {
+                "id": location.id,
+                "name": location.name,
+                "area": location.area.to_string(),
+                "count": location.appointments.count,
+            },
+            "portal": portal.map(|portal| serde_json::json!({
+                "name": portal.name,
+                "url": portal.url,
+            })),
+        });
+        self.client
+            .post(&self.url)
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use log::{error, warn};
+use tokio::time::{sleep, Duration};
+
+use crate::notifier::Notifier;
+use crate::{Location, Portal};
+
+/// Backoff schedule between redelivery attempts: 1m, 5m, then 15m (the cap).
+const BACKOFF_SECS: &[u64] = &[60, 300, 900];
+
+/// Attempt delivery, retrying transient failures on the [`BACKOFF_SECS`]
+/// schedule and dropping permanent ones, without ever returning an error that
+/// would take the polling loop offline.
+pub async fn deliver(notifier: &dyn Notifier, location: &Location, portal: Option<&Portal>) {
+    let mut backoff = BACKOFF_SECS.iter();
+    loop {
+        match notifier.notify(location, portal).await {
+            Ok(()) => return,
+            Err(e) if is_permanent(&e) => {
+                error!("Dropping notification after permanent failure: {:#}", e);
+                return;
+            }
+            Err(e) => match backoff.next() {
+                Some(&secs) => {
+                    warn!("Transient delivery failure, retrying in {}s: {:#}", secs, e);
+                    sleep(Duration::from_secs(secs)).await;
+                }
+                None => {
+                    error!("Giving up on notification after exhausting retries: {:#}", e);
+                    return;
+                }
+            },
+        }
+    }
+}
+
+/// Classify an error as permanent (and therefore not worth retrying). Bad
+/// credentials or a malformed address won't fix themselves, whereas network and
+/// timeout errors are assumed transient. We match on the concrete lettre error
+/// kinds rather than the error text so a transient failure whose message merely
+/// contains "invalid" isn't silently dropped.
+fn is_permanent(error: &anyhow::Error) -> bool {
+    // A 5xx SMTP reply (e.g. authentication rejected) is a permanent refusal; a
+    // 4xx reply, connection, or I/O error is transient.
+    if let Some(smtp) = error.downcast_ref::<lettre::smtp::error::Error>() {
+        return matches!(smtp, lettre::smtp::error::Error::Permanent(_));
+    }
+    // A malformed sender/recipient address can never be delivered.
+    error.downcast_ref::<lettre_email::error::Error>().is_some()
+}
@@ -0,0 +1,41 @@
+use anyhow::{ensure, Result};
+use std::env;
+use std::time::Duration;
+
+/// Where the dashboard feed is fetched from and how often.
+///
+/// Both fields come from the environment so the crate keeps working when the
+/// upstream URL changes or a user wants to watch a different region at a
+/// different cadence, rather than having TurboVax baked into the binary.
+#[derive(Debug)]
+pub struct Source {
+    pub url: String,
+    pub interval: Duration,
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Self {
+            url: "https://turbovax.global.ssl.fastly.net/dashboard".to_string(),
+            interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Source {
+    /// Build a source from `SOURCE_URL` and `POLL_INTERVAL_SECS`, falling back
+    /// to the TurboVax dashboard polled every 60 seconds.
+    pub fn from_env() -> Result<Self> {
+        let default = Source::default();
+        let url = env::var("SOURCE_URL").unwrap_or(default.url);
+        let interval = match env::var("POLL_INTERVAL_SECS") {
+            Ok(secs) => {
+                let secs: u64 = secs.parse()?;
+                ensure!(secs > 0, "POLL_INTERVAL_SECS must be greater than zero");
+                Duration::from_secs(secs)
+            }
+            Err(_) => default.interval,
+        };
+        Ok(Self { url, interval })
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::Location;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    updated_at: Option<DateTime<Utc>>,
+    last_available_at: Option<DateTime<Utc>>,
+}
+
+/// Persistent record of which availability events have already been notified.
+///
+/// Entries are keyed by `Location.id`; a location only warrants a fresh
+/// notification when its `updated_at`/`last_available_at` advanced past the
+/// spooled value. The spool is serialized to disk so a crash-loop doesn't
+/// re-notify everything on restart.
+#[derive(Debug)]
+pub struct Spool {
+    path: PathBuf,
+    entries: HashMap<String, Entry>,
+}
+
+impl Spool {
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { path, entries })
+    }
+
+    /// Record that `location` is currently available and report whether this is
+    /// a genuinely new availability event worth notifying about.
+    pub fn observe(&mut self, location: &Location) -> bool {
+        let fresh = match self.entries.get(&location.id) {
+            Some(entry) => {
+                location.last_available_at > entry.last_available_at
+                    || location.updated_at > entry.updated_at
+            }
+            None => true,
+        };
+        self.entries.insert(
+            location.id.clone(),
+            Entry {
+                updated_at: location.updated_at,
+                last_available_at: location.last_available_at,
+            },
+        );
+        fresh
+    }
+
+    /// Drop spooled entries for locations that are no longer available so they
+    /// notify again the next time a slot opens.
+    pub fn prune<'a>(&mut self, available: impl Iterator<Item = &'a str>) {
+        let live: HashSet<&str> = available.collect();
+        self.entries.retain(|id, _| live.contains(id.as_str()));
+    }
+
+    pub fn save(&self) -> Result<()> {
+        std::fs::write(&self.path, serde_json::to_string(&self.entries)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use serde::Deserialize;
+use std::collections::HashSet;
+
+use crate::{Area, Location, Portal, PortalType};
+
+/// A person watching the feed, identified by the address notifications are sent
+/// to and the slice of the city they care about.
+///
+/// An empty `areas` set matches every borough; a missing `portal_types` matches
+/// every portal type. This lets one deployment serve many people watching
+/// different parts of NYC.
+#[derive(Debug, Deserialize)]
+pub struct Subscriber {
+    pub email: String,
+    #[serde(default)]
+    areas: HashSet<Area>,
+    portal_types: Option<HashSet<PortalType>>,
+}
+
+impl Subscriber {
+    pub fn matches(&self, location: &Location, portal: Option<&Portal>) -> bool {
+        if !self.areas.is_empty() && !self.areas.contains(&location.area) {
+            return false;
+        }
+        match &self.portal_types {
+            Some(types) => portal.map_or(false, |portal| types.contains(&portal.portal_type)),
+            None => true,
+        }
+    }
+}